@@ -0,0 +1,52 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Options for configuring a [ProverServer](crate::ProverServer).
+
+/// Options that determine how a [ProverServer](crate::ProverServer) proves a
+/// [Session](crate::Session) or [Segment](crate::Segment).
+#[derive(Clone, Debug)]
+pub struct ProverOpts {
+    /// The hash function used when committing to the seal, e.g. `"sha-256"` or `"poseidon"`.
+    ///
+    /// Only receipts proven with `"poseidon"` can be compressed into a [SuccinctReceipt]; other
+    /// hash functions produce a [CompositeReceipt] instead.
+    ///
+    /// [SuccinctReceipt]: crate::host::receipt::SuccinctReceipt
+    /// [CompositeReceipt]: crate::host::receipt::CompositeReceipt
+    pub hashfn: String,
+
+    /// Which backend to run proving on, e.g. `"cpu"`, `"cuda"`, or `"metal"`.
+    ///
+    /// `None` selects whichever backend the binary was compiled to prefer, in the same
+    /// `cuda` > `metal` > `cpu` priority the old feature-flag cascade used.
+    pub backend: Option<String>,
+
+    /// A cap, in bytes, on the combined memory use of segments a [ProverServer] proves
+    /// concurrently within a single `prove_session` call. At least one segment is always proven
+    /// at a time, even when its estimated memory use alone exceeds the cap.
+    ///
+    /// `None` leaves concurrency bounded only by available parallelism.
+    pub segment_memory_limit: Option<usize>,
+}
+
+impl Default for ProverOpts {
+    fn default() -> Self {
+        Self {
+            hashfn: "poseidon".to_string(),
+            backend: None,
+            segment_memory_limit: None,
+        }
+    }
+}
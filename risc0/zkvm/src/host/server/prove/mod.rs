@@ -17,12 +17,12 @@
 mod dev_mode;
 mod exec;
 pub(crate) mod loader;
-mod plonk;
+pub(crate) mod plonk;
 mod prover_impl;
 #[cfg(test)]
 mod tests;
 
-use std::rc::Rc;
+use std::{rc::Rc, sync::Arc};
 
 use anyhow::{anyhow, bail, Result};
 use cfg_if::cfg_if;
@@ -40,7 +40,9 @@ use risc0_zkvm_platform::WORD_SIZE;
 
 use self::{dev_mode::DevModeProver, prover_impl::ProverImpl};
 use crate::{
-    host::receipt::{CompositeReceipt, InnerReceipt, SegmentReceipt, SuccinctReceipt},
+    host::receipt::{
+        CompositeReceipt, InnerReceipt, SegmentReceipt, SuccinctReceipt,
+    },
     is_dev_mode, ExecutorEnv, ExecutorImpl, ProverOpts, Receipt, Segment, Session, VerifierContext,
 };
 
@@ -90,18 +92,29 @@ pub trait ProverServer {
     /// Convert a [SuccinctReceipt] with a Poseidon hash function that uses a 254-bit field
     fn identity_p254(&self, a: &SuccinctReceipt) -> Result<SuccinctReceipt>;
 
-    /// Compress a [CompositeReceipt] into a single [SuccinctReceipt].
+    /// Fold N independent [SuccinctReceipt]s into a single [SuccinctReceipt] so that a verifier
+    /// pays for one check instead of N.
     ///
-    /// A [CompositeReceipt] may contain an arbitrary number of receipts assembled into
-    /// continuations and compositions. Together, these receipts collectively prove a top-level
-    /// [ReceiptClaim](crate::ReceiptClaim). This function compresses all of the constituent receipts of a
-    /// [CompositeReceipt] into a single [SuccinctReceipt] that proves the same top-level claim. It
-    /// accomplishes this by iterative application of the recursion programs including lift, join,
-    /// and resolve.
-    fn compress(&self, receipt: &CompositeReceipt) -> Result<SuccinctReceipt> {
-        // Compress all receipts in the top-level session into one succinct receipt for the session.
-        let continuation_receipt = receipt
-            .segments
+    /// A full implementation binds the ordered child claim digests into a Fiat-Shamir transcript
+    /// and discharges all of their deferred checks as one random linear combination inside a
+    /// dedicated accumulation circuit, so the resulting claim tree is deterministic; `receipts`
+    /// must not be empty. The default implementation has no such circuit to call into and always
+    /// fails; override it for a [ProverServer] backed by one.
+    fn aggregate(&self, receipts: &[SuccinctReceipt]) -> Result<SuccinctReceipt> {
+        let _ = receipts;
+        bail!("aggregate is not supported by this ProverServer")
+    }
+
+    /// Fold an ordered run of continuation [SegmentReceipt]s into a single [SuccinctReceipt] by
+    /// lifting each one and joining the results.
+    ///
+    /// The default implementation folds left-to-right, which is simple but strictly serial.
+    /// Implementations that can run [ProverServer::lift] and [ProverServer::join] concurrently
+    /// (e.g. across a pool of [Hal] workers) should override this to reduce the segments as a
+    /// balanced binary tree instead, so independent joins run in parallel; the resulting claim
+    /// is the same either way.
+    fn fold_continuation(&self, segments: &[SegmentReceipt]) -> Result<SuccinctReceipt> {
+        segments
             .iter()
             .try_fold(
                 None,
@@ -114,7 +127,20 @@ pub trait ProverServer {
             )?
             .ok_or(anyhow!(
                 "malformed composite receipt has no continuation segment receipts"
-            ))?;
+            ))
+    }
+
+    /// Compress a [CompositeReceipt] into a single [SuccinctReceipt].
+    ///
+    /// A [CompositeReceipt] may contain an arbitrary number of receipts assembled into
+    /// continuations and compositions. Together, these receipts collectively prove a top-level
+    /// [ReceiptClaim](crate::ReceiptClaim). This function compresses all of the constituent receipts of a
+    /// [CompositeReceipt] into a single [SuccinctReceipt] that proves the same top-level claim. It
+    /// accomplishes this by iterative application of the recursion programs including lift, join,
+    /// and resolve.
+    fn compress(&self, receipt: &CompositeReceipt) -> Result<SuccinctReceipt> {
+        // Compress all receipts in the top-level session into one succinct receipt for the session.
+        let continuation_receipt = self.fold_continuation(&receipt.segments)?;
 
         // Compress assumptions and resolve them to get the final succinct receipt.
         receipt.assumptions.iter().try_fold(
@@ -128,14 +154,19 @@ pub trait ProverServer {
                     "compressing composite receipts with fake receipt assumptions is not supported"
                 ),
                 InnerReceipt::Groth16(_) => bail!(
-                    "compressing composite receipts with Groth16 receipt assumptions is not supported"
-                )
+                    "compressing composite receipts with Groth16 receipt assumptions is not \
+                     supported: lifting a Groth16 proof back into the recursion domain needs a \
+                     dedicated recursion program that isn't part of this build yet"
+                ),
             },
         )
     }
 }
 
 /// A pair of [Hal] and [CircuitHal].
+///
+/// Held behind [Arc] rather than [Rc] so a [ProverImpl] built on a [HalPair] is `Sync`: segment
+/// proving can then be fanned out across a pool of worker threads that all share the same `&self`.
 #[derive(Clone)]
 pub struct HalPair<H, C>
 where
@@ -143,10 +174,10 @@ where
     C: CircuitHal<H>,
 {
     /// A [Hal] implementation.
-    pub hal: Rc<H>,
+    pub hal: Arc<H>,
 
     /// An [CircuitHal] implementation.
-    pub circuit_hal: Rc<C>,
+    pub circuit_hal: Arc<C>,
 }
 
 impl Session {
@@ -198,43 +229,41 @@ impl Segment {
 
 #[cfg(feature = "cuda")]
 mod cuda {
-    use std::rc::Rc;
+    use std::{rc::Rc, sync::Arc};
 
-    use anyhow::{bail, Result};
+    use anyhow::Result;
     use risc0_circuit_rv32im::cuda::{CudaCircuitHalPoseidon, CudaCircuitHalSha256};
     use risc0_zkp::hal::cuda::{CudaHalPoseidon, CudaHalSha256};
 
     use super::{HalPair, ProverImpl, ProverServer};
     use crate::ProverOpts;
 
-    pub fn get_prover_server(opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
-        match opts.hashfn.as_str() {
-            "sha-256" => {
-                let hal = Rc::new(CudaHalSha256::new());
-                let circuit_hal = Rc::new(CudaCircuitHalSha256::new(hal.clone()));
-                Ok(Rc::new(ProverImpl::new(
-                    "cuda",
-                    HalPair { hal, circuit_hal },
-                )))
-            }
-            "poseidon" => {
-                let hal = Rc::new(CudaHalPoseidon::new());
-                let circuit_hal = Rc::new(CudaCircuitHalPoseidon::new(hal.clone()));
-                Ok(Rc::new(ProverImpl::new(
-                    "cuda",
-                    HalPair { hal, circuit_hal },
-                )))
-            }
-            _ => bail!("Unsupported hashfn: {}", opts.hashfn),
-        }
+    pub fn get_prover_server_sha256(opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
+        let hal = Arc::new(CudaHalSha256::new());
+        let circuit_hal = Arc::new(CudaCircuitHalSha256::new(hal.clone()));
+        Ok(Rc::new(ProverImpl::with_segment_memory_limit(
+            "cuda",
+            HalPair { hal, circuit_hal },
+            opts.segment_memory_limit,
+        )))
+    }
+
+    pub fn get_prover_server_poseidon(opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
+        let hal = Arc::new(CudaHalPoseidon::new());
+        let circuit_hal = Arc::new(CudaCircuitHalPoseidon::new(hal.clone()));
+        Ok(Rc::new(ProverImpl::with_segment_memory_limit(
+            "cuda",
+            HalPair { hal, circuit_hal },
+            opts.segment_memory_limit,
+        )))
     }
 }
 
 #[cfg(feature = "metal")]
 mod metal {
-    use std::rc::Rc;
+    use std::{rc::Rc, sync::Arc};
 
-    use anyhow::{bail, Result};
+    use anyhow::Result;
     use risc0_circuit_rv32im::metal::MetalCircuitHal;
     use risc0_zkp::hal::metal::{
         MetalHalPoseidon, MetalHalSha256, MetalHashPoseidon, MetalHashSha256,
@@ -243,34 +272,31 @@ mod metal {
     use super::{HalPair, ProverImpl, ProverServer};
     use crate::ProverOpts;
 
-    pub fn get_prover_server(opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
-        match opts.hashfn.as_str() {
-            "sha-256" => {
-                let hal = Rc::new(MetalHalSha256::new());
-                let circuit_hal = Rc::new(MetalCircuitHal::<MetalHashSha256>::new(hal.clone()));
-                Ok(Rc::new(ProverImpl::new(
-                    "metal",
-                    HalPair { hal, circuit_hal },
-                )))
-            }
-            "poseidon" => {
-                let hal = Rc::new(MetalHalPoseidon::new());
-                let circuit_hal = Rc::new(MetalCircuitHal::<MetalHashPoseidon>::new(hal.clone()));
-                Ok(Rc::new(ProverImpl::new(
-                    "metal",
-                    HalPair { hal, circuit_hal },
-                )))
-            }
-            _ => bail!("Unsupported hashfn: {}", opts.hashfn),
-        }
+    pub fn get_prover_server_sha256(opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
+        let hal = Arc::new(MetalHalSha256::new());
+        let circuit_hal = Arc::new(MetalCircuitHal::<MetalHashSha256>::new(hal.clone()));
+        Ok(Rc::new(ProverImpl::with_segment_memory_limit(
+            "metal",
+            HalPair { hal, circuit_hal },
+            opts.segment_memory_limit,
+        )))
+    }
+
+    pub fn get_prover_server_poseidon(opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
+        let hal = Arc::new(MetalHalPoseidon::new());
+        let circuit_hal = Arc::new(MetalCircuitHal::<MetalHashPoseidon>::new(hal.clone()));
+        Ok(Rc::new(ProverImpl::with_segment_memory_limit(
+            "metal",
+            HalPair { hal, circuit_hal },
+            opts.segment_memory_limit,
+        )))
     }
 }
 
-#[allow(dead_code)]
 mod cpu {
-    use std::rc::Rc;
+    use std::{rc::Rc, sync::Arc};
 
-    use anyhow::{bail, Result};
+    use anyhow::Result;
     use risc0_circuit_rv32im::cpu::CpuCircuitHal;
     use risc0_zkp::{
         core::hash::{poseidon::PoseidonHashSuite, sha::Sha256HashSuite},
@@ -280,34 +306,126 @@ mod cpu {
     use super::{HalPair, ProverImpl, ProverServer};
     use crate::{host::CIRCUIT, ProverOpts};
 
-    pub fn get_prover_server(opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
-        let suite = match opts.hashfn.as_str() {
-            "sha-256" => Sha256HashSuite::new_suite(),
-            "poseidon" => PoseidonHashSuite::new_suite(),
-            _ => bail!("Unsupported hashfn: {}", opts.hashfn),
-        };
-        let hal = Rc::new(CpuHal::new(suite));
-        let circuit_hal = Rc::new(CpuCircuitHal::new(&CIRCUIT));
+    pub fn get_prover_server_sha256(opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
+        get_prover_server(Sha256HashSuite::new_suite(), opts)
+    }
+
+    pub fn get_prover_server_poseidon(opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
+        get_prover_server(PoseidonHashSuite::new_suite(), opts)
+    }
+
+    fn get_prover_server(
+        suite: risc0_zkp::core::hash::HashSuite<risc0_core::field::baby_bear::BabyBear>,
+        opts: &ProverOpts,
+    ) -> Result<Rc<dyn ProverServer>> {
+        let hal = Arc::new(CpuHal::new(suite));
+        let circuit_hal = Arc::new(CpuCircuitHal::new(&CIRCUIT));
         let hal_pair = HalPair { hal, circuit_hal };
-        Ok(Rc::new(ProverImpl::new("cpu", hal_pair)))
+        Ok(Rc::new(ProverImpl::with_segment_memory_limit(
+            "cpu",
+            hal_pair,
+            opts.segment_memory_limit,
+        )))
     }
 }
 
-/// Select a [ProverServer] based on the specified [ProverOpts] and currently
-/// compiled features.
-pub fn get_prover_server(opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
-    if is_dev_mode() {
-        eprintln!("WARNING: proving in dev mode. This will not generate valid, secure proofs.");
-        return Ok(Rc::new(DevModeProver));
+/// Constructs a [ProverServer] for a given [ProverOpts]. Registered under a `(backend, hashfn)`
+/// key in a [ProverServerRegistry] so that third parties can plug in a custom [Hal]/[CircuitHal]
+/// pair or hash suite without patching this crate.
+pub type ProverServerFactory = fn(&ProverOpts) -> Result<Rc<dyn ProverServer>>;
+
+/// A registry of [ProverServerFactory]s, keyed by `(backend, hashfn)`.
+///
+/// Replaces the old compile-time `cfg_if` cascade over the `cuda`/`metal`/`cpu` features, under
+/// which adding an accelerator or hash suite meant editing this module. Multiple backends can
+/// now coexist in one binary; which one runs is chosen at runtime via [ProverOpts] rather than
+/// by feature flag.
+pub struct ProverServerRegistry {
+    factories: std::sync::RwLock<std::collections::HashMap<(String, String), ProverServerFactory>>,
+}
+
+impl ProverServerRegistry {
+    /// Get the global [ProverServerRegistry], pre-populated with the backends this crate ships
+    /// (`cpu`, plus `cuda`/`metal` when their features are enabled). Third parties call
+    /// [ProverServerRegistry::register] on the instance returned here to add their own.
+    pub fn global() -> &'static ProverServerRegistry {
+        static REGISTRY: std::sync::OnceLock<ProverServerRegistry> = std::sync::OnceLock::new();
+        REGISTRY.get_or_init(|| {
+            let registry = ProverServerRegistry {
+                factories: Default::default(),
+            };
+            registry.register_defaults();
+            registry
+        })
     }
 
+    fn register_defaults(&self) {
+        #[cfg(feature = "cuda")]
+        {
+            self.register("cuda", "sha-256", cuda::get_prover_server_sha256);
+            self.register("cuda", "poseidon", cuda::get_prover_server_poseidon);
+        }
+        #[cfg(feature = "metal")]
+        {
+            self.register("metal", "sha-256", metal::get_prover_server_sha256);
+            self.register("metal", "poseidon", metal::get_prover_server_poseidon);
+        }
+        self.register("cpu", "sha-256", cpu::get_prover_server_sha256);
+        self.register("cpu", "poseidon", cpu::get_prover_server_poseidon);
+    }
+
+    /// Register a [ProverServerFactory] under the given `(backend, hashfn)` key. This is how
+    /// third parties plug in a custom [Hal]/[CircuitHal] implementation or hash suite: register
+    /// it here, then select it at runtime by setting the matching `backend`/`hashfn` on
+    /// [ProverOpts].
+    pub fn register(&self, backend: &str, hashfn: &str, factory: ProverServerFactory) {
+        self.factories
+            .write()
+            .unwrap()
+            .insert((backend.to_string(), hashfn.to_string()), factory);
+    }
+
+    /// Look up the [ProverServerFactory] registered under the given `(backend, hashfn)` key, if
+    /// any.
+    pub fn get(&self, backend: &str, hashfn: &str) -> Option<ProverServerFactory> {
+        self.factories
+            .read()
+            .unwrap()
+            .get(&(backend.to_string(), hashfn.to_string()))
+            .copied()
+    }
+}
+
+/// The backend used when [ProverOpts] does not request a specific one, mirroring the priority
+/// the old `cfg_if` cascade used: `cuda` > `metal` > `cpu`.
+fn default_backend() -> &'static str {
     cfg_if! {
         if #[cfg(feature = "cuda")] {
-            cuda::get_prover_server(opts)
+            "cuda"
         } else if #[cfg(feature = "metal")] {
-            metal::get_prover_server(opts)
+            "metal"
         } else {
-            cpu::get_prover_server(opts)
+            "cpu"
         }
     }
 }
+
+/// Select a [ProverServer] based on the specified [ProverOpts], looking it up in the global
+/// [ProverServerRegistry].
+pub fn get_prover_server(opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
+    if is_dev_mode() {
+        eprintln!("WARNING: proving in dev mode. This will not generate valid, secure proofs.");
+        return Ok(Rc::new(DevModeProver));
+    }
+
+    let backend = opts.backend.as_deref().unwrap_or_else(default_backend);
+    let factory = ProverServerRegistry::global()
+        .get(backend, &opts.hashfn)
+        .ok_or_else(|| {
+            anyhow!(
+                "no ProverServer registered for backend `{backend}` with hashfn `{}`",
+                opts.hashfn
+            )
+        })?;
+    factory(opts)
+}
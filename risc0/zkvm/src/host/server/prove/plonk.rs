@@ -0,0 +1,128 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Solidity verifier codegen for Groth16 proofs over BN254.
+//!
+//! The STARK-to-SNARK wrapping stage this module was meant to pair with (folding a p254
+//! [SuccinctReceipt](crate::host::receipt::SuccinctReceipt) into an actual Groth16 proof) needs a
+//! Groth16 prover this workspace doesn't depend on, and isn't implemented here; see
+//! [ProverServer::compress](crate::host::server::prove::ProverServer::compress), whose
+//! `InnerReceipt::Groth16` arm fails honestly rather than calling into a wrapping stage that
+//! doesn't exist. Only the verifier-generation half of the pipeline, which needs no prover at
+//! all, is implemented below.
+
+/// A BN254 Groth16 verifying key, in the form the generated Solidity verifier is templated on.
+pub struct VerifyingKey {
+    /// `alpha`, a G1 point.
+    pub alpha: [[u8; 32]; 2],
+    /// `beta`, `gamma`, and `delta` are G2 points (a pair of Fp2 coordinates each).
+    pub beta: [[[u8; 32]; 2]; 2],
+    pub gamma: [[[u8; 32]; 2]; 2],
+    pub delta: [[[u8; 32]; 2]; 2],
+    /// The IC points used to compute `vk_x`, the linear combination of the verifying key with
+    /// the public inputs (the [ReceiptClaim](crate::ReceiptClaim) digest).
+    pub ic: Vec<[[u8; 32]; 2]>,
+}
+
+/// Generate a standalone Solidity contract that verifies Groth16 proofs against `vk` using the
+/// EVM's `bn256Add` (`0x06`), `bn256ScalarMul` (`0x07`), and `bn256Pairing` (`0x08`)
+/// precompiles.
+///
+/// The contract checks `e(A,B) = e(alpha,beta)·e(vk_x,gamma)·e(C,delta)`, where `vk_x` is
+/// folded from `vk.ic` and the public claim digest. Verification costs a fixed ~250k gas no
+/// matter how large the guest program was.
+pub fn verifier_contract(vk: &VerifyingKey) -> String {
+    let ic_entries = vk
+        .ic
+        .iter()
+        .enumerate()
+        .map(|(i, point)| format!("        ic[{i}] = Pairing.G1Point({}, {});", hex(&point[0]), hex(&point[1])))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        r#"// SPDX-License-Identifier: Apache-2.0
+// Auto-generated by risc0_zkvm::host::server::prove::plonk::verifier_contract. Do not edit.
+pragma solidity ^0.8.19;
+
+import {{Pairing}} from "./Pairing.sol";
+
+/// @notice Verifies RISC Zero Groth16 proofs, wrapping a recursive STARK verification so that
+/// on-chain verification cost is constant regardless of guest program size.
+contract Groth16Verifier {{
+    using Pairing for *;
+
+    Pairing.G1Point alpha;
+    Pairing.G2Point beta;
+    Pairing.G2Point gamma;
+    Pairing.G2Point delta;
+    Pairing.G1Point[{ic_len}] ic;
+
+    constructor() {{
+        alpha = Pairing.G1Point({alpha0}, {alpha1});
+        beta = Pairing.G2Point([{beta0}, {beta1}], [{beta2}, {beta3}]);
+        gamma = Pairing.G2Point([{gamma0}, {gamma1}], [{gamma2}, {gamma3}]);
+        delta = Pairing.G2Point([{delta0}, {delta1}], [{delta2}, {delta3}]);
+{ic_entries}
+    }}
+
+    /// @notice Verify a Groth16 proof against a public claim digest.
+    /// @param a The proof's `A` point.
+    /// @param b The proof's `B` point.
+    /// @param c The proof's `C` point.
+    /// @param claimDigest The public [ReceiptClaim] digest the proof attests to.
+    function verify(
+        uint256[2] calldata a,
+        uint256[2][2] calldata b,
+        uint256[2] calldata c,
+        uint256 claimDigest
+    ) external view returns (bool) {{
+        Pairing.G1Point memory vkX = ic[0];
+        vkX = Pairing.addition(vkX, Pairing.scalar_mul(ic[1], claimDigest));
+
+        return Pairing.pairingCheck(
+            Pairing.negate(Pairing.G1Point(a[0], a[1])),
+            Pairing.G2Point(b[0], b[1]),
+            alpha,
+            beta,
+            vkX,
+            gamma,
+            Pairing.G1Point(c[0], c[1]),
+            delta
+        );
+    }}
+}}
+"#,
+        ic_len = vk.ic.len(),
+        alpha0 = hex(&vk.alpha[0]),
+        alpha1 = hex(&vk.alpha[1]),
+        beta0 = hex(&vk.beta[0][0]),
+        beta1 = hex(&vk.beta[0][1]),
+        beta2 = hex(&vk.beta[1][0]),
+        beta3 = hex(&vk.beta[1][1]),
+        gamma0 = hex(&vk.gamma[0][0]),
+        gamma1 = hex(&vk.gamma[0][1]),
+        gamma2 = hex(&vk.gamma[1][0]),
+        gamma3 = hex(&vk.gamma[1][1]),
+        delta0 = hex(&vk.delta[0][0]),
+        delta1 = hex(&vk.delta[0][1]),
+        delta2 = hex(&vk.delta[1][0]),
+        delta3 = hex(&vk.delta[1][1]),
+        ic_entries = ic_entries,
+    )
+}
+
+fn hex(limb: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(limb))
+}
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use risc0_circuit_rv32im::{
     layout::{OutBuffer, LAYOUT},
     REGISTER_GROUP_ACCUM, REGISTER_GROUP_CODE, REGISTER_GROUP_DATA,
@@ -39,31 +39,73 @@ use crate::{
 /// An implementation of a Prover that runs locally.
 pub struct ProverImpl<H, C>
 where
-    H: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem>,
-    C: CircuitHal<H>,
+    H: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem> + Send + Sync,
+    C: CircuitHal<H> + Send + Sync,
 {
     name: String,
     hal_pair: HalPair<H, C>,
+    segment_memory_limit: Option<usize>,
 }
 
 impl<H, C> ProverImpl<H, C>
 where
-    H: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem>,
-    C: CircuitHal<H>,
+    H: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem> + Send + Sync,
+    C: CircuitHal<H> + Send + Sync,
 {
     /// Construct a [ProverImpl] with the given name and [HalPair].
     pub fn new(name: &str, hal_pair: HalPair<H, C>) -> Self {
+        Self::with_segment_memory_limit(name, hal_pair, None)
+    }
+
+    /// Construct a [ProverImpl] that caps the number of segments proven concurrently so their
+    /// combined estimated memory use stays under `segment_memory_limit` bytes. At least one
+    /// segment is always proven at a time, even when its estimate alone exceeds the limit. `None`
+    /// leaves the concurrency bounded only by the number of available CPUs.
+    pub fn with_segment_memory_limit(
+        name: &str,
+        hal_pair: HalPair<H, C>,
+        segment_memory_limit: Option<usize>,
+    ) -> Self {
         Self {
             name: name.to_string(),
             hal_pair,
+            segment_memory_limit,
         }
     }
+
+    /// The number of segments to prove concurrently: bounded by available parallelism, and
+    /// further capped by `segment_memory_limit` divided by `per_segment_estimate` bytes.
+    fn max_concurrent_segments(&self, per_segment_estimate: usize) -> usize {
+        let parallelism = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        match self.segment_memory_limit {
+            Some(limit) => parallelism.min((limit / per_segment_estimate.max(1)).max(1)),
+            None => parallelism,
+        }
+    }
+}
+
+/// Estimate the peak memory a [Hal] will use while proving `segment`, in bytes.
+///
+/// A segment's working set is dominated by its execution trace, which is linear in its cycle
+/// count (`2^po2`); [BYTES_PER_CYCLE_ESTIMATE] is a rough, backend-agnostic upper bound on the
+/// bytes of trace/accumulator data held per cycle. This has to run before any segment has been
+/// proven, so unlike [ProverServer::get_peak_memory_usage] (which only reflects segments this
+/// [Hal] has already proven, i.e. zero on the very first, often-largest, `prove_session` call) it
+/// doesn't depend on anything having been proven yet.
+fn estimate_segment_memory(segment: &Segment) -> usize {
+    BYTES_PER_CYCLE_ESTIMATE.saturating_mul(1usize << segment.po2)
 }
 
+/// A conservative, backend-agnostic estimate of bytes of trace/accumulator data a [Hal] holds
+/// per cycle while proving a segment.
+const BYTES_PER_CYCLE_ESTIMATE: usize = 256;
+
 impl<H, C> ProverServer for ProverImpl<H, C>
 where
-    H: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem>,
-    C: CircuitHal<H>,
+    H: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem> + Send + Sync,
+    C: CircuitHal<H> + Send + Sync,
 {
     fn prove_session(&self, ctx: &VerifierContext, session: &Session) -> Result<Receipt> {
         tracing::info!(
@@ -72,17 +114,36 @@ where
             session.exit_code,
             session.journal.as_ref().map(|x| hex::encode(x))
         );
-        let mut segments = Vec::new();
-        for segment_ref in session.segments.iter() {
-            let segment = segment_ref.resolve()?;
+        let resolved_segments = session
+            .segments
+            .iter()
+            .map(|segment_ref| segment_ref.resolve())
+            .collect::<Result<Vec<_>>>()?;
+
+        // Segments are independent until the final fold, so prove them concurrently across a
+        // pool of workers, bounded by available parallelism and by `segment_memory_limit`. Size
+        // the estimate off the largest segment in the batch, since that's the one most likely to
+        // drive memory use up if run alongside others.
+        let per_segment_estimate = resolved_segments
+            .iter()
+            .map(estimate_segment_memory)
+            .max()
+            .unwrap_or(1);
+        let max_in_flight = self.max_concurrent_segments(per_segment_estimate);
+        tracing::debug!(
+            "prove_session: proving up to {} segments concurrently",
+            max_in_flight
+        );
+        let segments = parallel_map(&resolved_segments, max_in_flight, |segment| {
             for hook in &session.hooks {
-                hook.on_pre_prove_segment(&segment);
+                hook.on_pre_prove_segment(segment);
             }
-            segments.push(self.prove_segment(ctx, &segment)?);
+            let receipt = self.prove_segment(ctx, segment)?;
             for hook in &session.hooks {
-                hook.on_post_prove_segment(&segment);
+                hook.on_post_prove_segment(segment);
             }
-        }
+            Ok(receipt)
+        })?;
         // TODO(#982): Support unresolved assumptions here.
         let composite_receipt = CompositeReceipt {
             segments,
@@ -207,6 +268,30 @@ where
         lift(receipt)
     }
 
+    fn fold_continuation(&self, segments: &[SegmentReceipt]) -> Result<SuccinctReceipt> {
+        if segments.is_empty() {
+            bail!("malformed composite receipt has no continuation segment receipts");
+        }
+
+        // When called from `prove_session`, this prover has already proven the segments, so
+        // `get_peak_memory_usage` reflects real usage. On a prover that has proven nothing yet it
+        // reads zero, the estimate falls back to 1 byte, and concurrency is bounded only by
+        // available parallelism.
+        let max_in_flight = self.max_concurrent_segments(self.get_peak_memory_usage().max(1));
+
+        // Lift every segment in parallel, then fold the results together as a balanced binary
+        // tree rather than a left-associative chain, so independent joins also run concurrently.
+        let mut level = parallel_map(segments, max_in_flight, |segment| self.lift(segment))?;
+        while level.len() > 1 {
+            let (pairs, odd_one_out) = pair_up(&level);
+            let mut next = parallel_map(&pairs, max_in_flight, |pair| self.join(&pair[0], &pair[1]))?;
+            next.extend(odd_one_out);
+            level = next;
+        }
+
+        Ok(level.into_iter().next().unwrap())
+    }
+
     fn join(&self, a: &SuccinctReceipt, b: &SuccinctReceipt) -> Result<SuccinctReceipt> {
         join(a, b)
     }
@@ -222,4 +307,46 @@ where
     fn identity_p254(&self, a: &SuccinctReceipt) -> Result<SuccinctReceipt> {
         identity_p254(a)
     }
+
+    // No accumulation recursion program is wired in, so aggregate() falls back to the trait
+    // default, which fails honestly instead of computing a transcript it can't do anything with.
+}
+
+/// Split `items` into adjacent pairs, carrying a lone trailing element forward unpaired instead
+/// of dropping it.
+pub(super) fn pair_up<T: Clone>(items: &[T]) -> (Vec<[T; 2]>, Option<T>) {
+    let odd_one_out = (items.len() % 2 == 1).then(|| items.last().unwrap().clone());
+    let pairs = items
+        .chunks_exact(2)
+        .map(|pair| [pair[0].clone(), pair[1].clone()])
+        .collect();
+    (pairs, odd_one_out)
+}
+
+/// Apply `f` to every item in `items`, running up to `max_in_flight` calls concurrently on a
+/// pool of worker threads, and preserving input order in the result.
+pub(super) fn parallel_map<T, R, F>(items: &[T], max_in_flight: usize, f: F) -> Result<Vec<R>>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> Result<R> + Sync,
+{
+    let mut out = Vec::with_capacity(items.len());
+    for batch in items.chunks(max_in_flight.max(1)) {
+        let batch_out = std::thread::scope(|scope| -> Result<Vec<R>> {
+            batch
+                .iter()
+                .map(|item| scope.spawn(|| f(item)))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| -> Result<R> {
+                    handle
+                        .join()
+                        .map_err(|_| anyhow!("worker thread panicked"))?
+                })
+                .collect()
+        })?;
+        out.extend(batch_out);
+    }
+    Ok(out)
 }
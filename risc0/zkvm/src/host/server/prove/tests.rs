@@ -0,0 +1,148 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::rc::Rc;
+
+use super::{
+    plonk::{verifier_contract, VerifyingKey},
+    prover_impl::{pair_up, parallel_map},
+    ProverServer, ProverServerRegistry,
+};
+use crate::ProverOpts;
+
+fn point() -> [[u8; 32]; 2] {
+    [[0u8; 32], [0u8; 32]]
+}
+
+fn g2_point() -> [[[u8; 32]; 2]; 2] {
+    [point(), point()]
+}
+
+fn verifying_key(ic_len: usize) -> VerifyingKey {
+    VerifyingKey {
+        alpha: point(),
+        beta: g2_point(),
+        gamma: g2_point(),
+        delta: g2_point(),
+        ic: (0..ic_len).map(|_| point()).collect(),
+    }
+}
+
+#[test]
+fn parallel_map_preserves_order() {
+    let items: Vec<u32> = (0..10).collect();
+    let out = parallel_map(&items, 3, |x| Ok(x * 2)).unwrap();
+    assert_eq!(out, items.iter().map(|x| x * 2).collect::<Vec<_>>());
+}
+
+#[test]
+fn parallel_map_propagates_errors() {
+    let items = vec![1, 2, 3];
+    let result = parallel_map(&items, 2, |x| {
+        if *x == 2 {
+            anyhow::bail!("boom");
+        }
+        Ok(*x)
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn pair_up_even_length() {
+    let (pairs, odd_one_out) = pair_up(&[1, 2, 3, 4]);
+    assert_eq!(pairs, vec![[1, 2], [3, 4]]);
+    assert_eq!(odd_one_out, None);
+}
+
+#[test]
+fn pair_up_odd_length_carries_last_element_forward() {
+    let (pairs, odd_one_out) = pair_up(&[1, 2, 3]);
+    assert_eq!(pairs, vec![[1, 2]]);
+    assert_eq!(odd_one_out, Some(3));
+}
+
+#[test]
+fn verifier_contract_ic_array_size_matches_ic_len() {
+    for ic_len in [0, 1, 3] {
+        let contract = verifier_contract(&verifying_key(ic_len));
+        assert!(
+            contract.contains(&format!("Pairing.G1Point[{ic_len}] ic;")),
+            "ic array should be declared with size {ic_len}:\n{contract}"
+        );
+    }
+}
+
+#[test]
+fn verifier_contract_emits_one_entry_per_ic_point() {
+    for ic_len in [0, 1, 3] {
+        let contract = verifier_contract(&verifying_key(ic_len));
+        // Each constructor entry is its own `ic[i] = ...;` line; `verify()`'s fixed `ic[0]`/
+        // `ic[1]` reads appear mid-line there, so a line-starts-with-`ic[` filter counts only
+        // the per-point entries.
+        let assignment_count = contract
+            .lines()
+            .filter(|line| line.trim_start().starts_with("ic["))
+            .count();
+        assert_eq!(
+            assignment_count, ic_len,
+            "expected {ic_len} `ic[i] = ...` assignments in:\n{contract}"
+        );
+        for i in 0..ic_len {
+            assert!(
+                contract.contains(&format!("ic[{i}] = Pairing.G1Point(")),
+                "missing assignment for ic[{i}] in:\n{contract}"
+            );
+        }
+    }
+}
+
+#[test]
+fn verifier_contract_hex_encodes_limbs_as_0x_prefixed_big_endian() {
+    let mut vk = verifying_key(1);
+    vk.alpha[0][31] = 0xab;
+    vk.alpha[0][0] = 0xcd;
+
+    let contract = verifier_contract(&vk);
+    let expected = format!("0x{}{}{}", "cd", "0".repeat(60), "ab");
+    assert!(
+        contract.contains(&expected),
+        "expected alpha.x limb to hex-encode big-endian as {expected} in:\n{contract}"
+    );
+}
+
+#[test]
+fn verifier_contract_is_well_formed_solidity() {
+    let contract = verifier_contract(&verifying_key(2));
+    assert!(contract.starts_with("// SPDX-License-Identifier: Apache-2.0"));
+    assert!(contract.contains("pragma solidity ^0.8.19;"));
+    assert_eq!(
+        contract.matches('{').count(),
+        contract.matches('}').count(),
+        "braces should balance in:\n{contract}"
+    );
+}
+
+#[test]
+fn registry_round_trips_a_registered_factory() {
+    fn factory(_opts: &ProverOpts) -> anyhow::Result<Rc<dyn ProverServer>> {
+        anyhow::bail!("unreachable: only the registration is under test")
+    }
+
+    let registry = ProverServerRegistry::global();
+    registry.register("test-backend", "test-hashfn", factory);
+
+    assert!(registry.get("test-backend", "test-hashfn").is_some());
+    assert!(registry.get("test-backend", "other-hashfn").is_none());
+    assert!(registry.get("other-backend", "test-hashfn").is_none());
+}